@@ -0,0 +1,51 @@
+//! Types allowing customization of the flows provided by this crate.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// Contains the information necessary to prompt the user to authorize via the device flow.
+#[derive(Debug, Clone)]
+pub struct PollInformation {
+    /// The code the user must enter into the verification URL.
+    pub user_code: String,
+    /// The URL the user should visit to authorize this application.
+    pub verification_url: String,
+    /// The URL the user should visit to authorize this application, with `user_code` already
+    /// embedded as a query parameter. Lets the user skip typing the code manually, e.g. by
+    /// rendering this as a QR code. Not every provider returns this field.
+    pub verification_url_complete: Option<String>,
+    /// The server-side deadline after which `device_code` is no longer valid.
+    pub expires_at: DateTime<Utc>,
+    /// The interval at which the device flow should poll the token endpoint.
+    pub interval: Duration,
+}
+
+/// Implement this trait to customize how a device-flow client interacts with the user, e.g.
+/// to show the user code in a UI rather than on the terminal.
+pub trait DeviceFlowDelegate: Send + Sync {
+    /// Called once the device and user codes have been obtained from the server. Should show
+    /// `pi.user_code` and `pi.verification_url` (or `pi.verification_url_complete`, e.g. as a
+    /// QR code) to the user.
+    fn present_user_code(&self, pi: &PollInformation);
+}
+
+/// The default `DeviceFlowDelegate`. Prints the user code and verification URL to stderr.
+pub struct DefaultDeviceFlowDelegate;
+
+impl DeviceFlowDelegate for DefaultDeviceFlowDelegate {
+    fn present_user_code(&self, pi: &PollInformation) {
+        if let Some(verification_url_complete) = &pi.verification_url_complete {
+            eprintln!(
+                "Please visit {} to authorize this application (already contains your \
+                 user code).",
+                verification_url_complete
+            );
+        } else {
+            eprintln!(
+                "Please enter code {} at {} to authorize this application.",
+                pi.user_code, pi.verification_url
+            );
+        }
+    }
+}
@@ -18,6 +18,99 @@ pub const GOOGLE_DEVICE_CODE_URL: &str = "https://accounts.google.com/o/oauth2/d
 // https://developers.google.com/identity/protocols/OAuth2ForDevices#step-4:-poll-googles-authorization-server
 pub const GOOGLE_GRANT_TYPE: &str = "http://oauth.net/grant_type/device/1.0";
 
+pub const GITHUB_DEVICE_CODE_URL: &str = "https://github.com/login/device/code";
+
+// https://docs.github.com/en/apps/creating-github-apps/authenticating-with-a-github-app/authorizing-github-apps#device-flow
+pub const GITHUB_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Describes how a specific authorization server's device flow deviates from the reference
+/// implementation (Google's), so that [`DeviceFlow`] can drive other RFC 8628 servers too.
+#[derive(Clone, Debug)]
+pub struct DeviceFlowProvider {
+    /// The URL to request a device and user code from.
+    pub device_code_url: Cow<'static, str>,
+    /// The `grant_type` to send when polling the token endpoint.
+    pub grant_type: Cow<'static, str>,
+    /// Whether `client_secret` should be sent when polling the token endpoint. Public clients
+    /// (e.g. GitHub's) omit it.
+    pub send_client_secret: bool,
+    /// A `User-Agent` header value to send on every request. Some providers (e.g. GitHub)
+    /// reject requests without one.
+    pub user_agent: Option<Cow<'static, str>>,
+    /// Whether to send `Accept: application/json`, so a provider that otherwise replies with
+    /// `application/x-www-form-urlencoded` (e.g. GitHub) returns JSON instead.
+    pub accept_json: bool,
+}
+
+impl DeviceFlowProvider {
+    /// The provider descriptor matching Google's device flow implementation.
+    pub const GOOGLE: DeviceFlowProvider = DeviceFlowProvider {
+        device_code_url: Cow::Borrowed(GOOGLE_DEVICE_CODE_URL),
+        grant_type: Cow::Borrowed(GOOGLE_GRANT_TYPE),
+        send_client_secret: true,
+        user_agent: None,
+        accept_json: false,
+    };
+
+    /// The provider descriptor matching [GitHub's device flow implementation](https://docs.github.com/en/developers/apps/building-github-apps/identifying-and-authorizing-users-for-github-apps#device-flow).
+    pub const GITHUB: DeviceFlowProvider = DeviceFlowProvider {
+        device_code_url: Cow::Borrowed(GITHUB_DEVICE_CODE_URL),
+        grant_type: Cow::Borrowed(GITHUB_GRANT_TYPE),
+        send_client_secret: false,
+        user_agent: Some(Cow::Borrowed("yup-oauth2")),
+        accept_json: true,
+    };
+}
+
+impl Default for DeviceFlowProvider {
+    fn default() -> Self {
+        DeviceFlowProvider::GOOGLE
+    }
+}
+
+/// The `error` values a device flow token endpoint can return, per
+/// [RFC 8628 §3.5](https://tools.ietf.org/html/rfc8628#section-3.5).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceFlowError {
+    /// The user refused to authorize the client.
+    AccessDenied,
+    /// The `device_code` has expired; the client must restart the flow from the beginning.
+    /// This is the server's own view of expiry; `Error::DeviceFlowExpired` is raised instead
+    /// when we detect the `PollInformation::expires_at` deadline locally, before ever making
+    /// this round trip.
+    ExpiredToken,
+    /// The authorization request is still pending as the user hasn't yet completed it.
+    Pending,
+    /// The client is polling too fast and should slow down.
+    SlowDown,
+    /// Any other error code returned by the token endpoint.
+    Other(String),
+}
+
+impl DeviceFlowError {
+    fn from_error_code(error: &str) -> Self {
+        match error {
+            "access_denied" => DeviceFlowError::AccessDenied,
+            "expired_token" => DeviceFlowError::ExpiredToken,
+            "authorization_pending" => DeviceFlowError::Pending,
+            "slow_down" => DeviceFlowError::SlowDown,
+            other => DeviceFlowError::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for DeviceFlowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeviceFlowError::AccessDenied => write!(f, "the user denied the authorization request"),
+            DeviceFlowError::ExpiredToken => write!(f, "the device code has expired"),
+            DeviceFlowError::Pending => write!(f, "the authorization request is still pending"),
+            DeviceFlowError::SlowDown => write!(f, "polling too fast; slow down"),
+            DeviceFlowError::Other(error) => write!(f, "{}", error),
+        }
+    }
+}
+
 /// Implements the [Oauth2 Device Flow](https://developers.google.com/youtube/v3/guides/authentication#devices)
 /// It operates in two steps:
 /// * obtain a code to show to the user
@@ -27,21 +120,39 @@ pub struct DeviceFlow {
     pub(crate) device_code_url: Cow<'static, str>,
     pub(crate) flow_delegate: Box<dyn DeviceFlowDelegate>,
     pub(crate) grant_type: Cow<'static, str>,
+    pub(crate) provider: DeviceFlowProvider,
 }
 
 impl DeviceFlow {
     /// Create a new DeviceFlow. The default FlowDelegate will be used and the
-    /// default wait time is 120 seconds.
-    pub(crate) fn new(app_secret: ApplicationSecret) -> Self {
+    /// default wait time is 120 seconds. Chain `with_provider` to authenticate against a
+    /// non-Google device flow, e.g. `DeviceFlow::new(app_secret).with_provider(DeviceFlowProvider::GITHUB)`.
+    pub fn new(app_secret: ApplicationSecret) -> Self {
+        let provider = DeviceFlowProvider::default();
         DeviceFlow {
             app_secret,
-            device_code_url: GOOGLE_DEVICE_CODE_URL.into(),
+            device_code_url: provider.device_code_url.clone(),
             flow_delegate: Box::new(DefaultDeviceFlowDelegate),
-            grant_type: GOOGLE_GRANT_TYPE.into(),
+            grant_type: provider.grant_type.clone(),
+            provider,
         }
     }
 
-    pub(crate) async fn token<C, T>(
+    /// Use a different `DeviceFlowProvider`, e.g. `DeviceFlowProvider::GITHUB`, instead of the
+    /// default Google-compatible one. This is how a caller authenticates against GitHub's
+    /// device flow instead of Google's with the same `DeviceFlow`: it also switches
+    /// `device_code_url`/`grant_type` to the provider's own endpoint and grant type.
+    pub fn with_provider(mut self, provider: DeviceFlowProvider) -> Self {
+        self.device_code_url = provider.device_code_url.clone();
+        self.grant_type = provider.grant_type.clone();
+        self.provider = provider;
+        self
+    }
+
+    /// Run the device flow to completion: obtain a code, show it to the user via the
+    /// configured `DeviceFlowDelegate`, then poll until the user has authorized (or a terminal
+    /// error occurs).
+    pub async fn token<C, T>(
         &self,
         hyper_client: &hyper::Client<C>,
         scopes: &[T],
@@ -54,6 +165,7 @@ impl DeviceFlow {
             &self.app_secret,
             hyper_client,
             &self.device_code_url,
+            &self.provider,
             scopes,
         )
         .await?;
@@ -64,6 +176,7 @@ impl DeviceFlow {
             &pollinf,
             &device_code,
             &self.grant_type,
+            &self.provider,
         )
         .await
     }
@@ -75,27 +188,33 @@ impl DeviceFlow {
         pollinf: &PollInformation,
         device_code: &str,
         grant_type: &str,
+        provider: &DeviceFlowProvider,
     ) -> Result<Token, Error>
     where
         C: hyper::client::connect::Connect + 'static,
     {
         let mut interval = pollinf.interval;
         loop {
+            if Utc::now() >= pollinf.expires_at {
+                return Err(Error::DeviceFlowExpired);
+            }
             tokio::timer::delay_for(interval).await;
-            interval = match Self::poll_token(&app_secret, hyper_client, device_code, grant_type)
-                .await
-            {
-                Ok(token) => return Ok(token),
-                Err(Error::AuthError(AuthError { error, .. }))
-                    if error.as_str() == "authorization_pending" =>
+            interval =
+                match Self::poll_token(&app_secret, hyper_client, device_code, grant_type, provider)
+                    .await
                 {
-                    interval
+                    Ok(token) => return Ok(token),
+                    Err(Error::AuthError(auth_error)) => {
+                        match DeviceFlowError::from_error_code(&auth_error.error) {
+                            DeviceFlowError::Pending => interval,
+                            DeviceFlowError::SlowDown => {
+                                Self::next_slow_down_interval(interval, &auth_error)
+                            }
+                            terminal => return Err(Error::DeviceFlow(terminal)),
+                        }
+                    }
+                    Err(err) => return Err(err),
                 }
-                Err(Error::AuthError(AuthError { error, .. })) if error.as_str() == "slow_down" => {
-                    interval + Duration::from_secs(5)
-                }
-                Err(err) => return Err(err),
-            }
         }
     }
 
@@ -118,6 +237,7 @@ impl DeviceFlow {
         application_secret: &ApplicationSecret,
         client: &hyper::Client<C>,
         device_code_url: &str,
+        provider: &DeviceFlowProvider,
         scopes: &[T],
     ) -> Result<(PollInformation, String), Error>
     where
@@ -133,21 +253,24 @@ impl DeviceFlow {
 
         // note: works around bug in rustlang
         // https://github.com/rust-lang/rust/issues/22252
-        let req = hyper::Request::post(device_code_url)
-            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-            .body(hyper::Body::from(req))
-            .unwrap();
+        let mut builder = hyper::Request::post(device_code_url)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+        builder = Self::apply_provider_headers(builder, provider);
+        let req = builder.body(hyper::Body::from(req)).unwrap();
         let resp = client.request(req).await?;
         // This return type is defined in https://tools.ietf.org/html/draft-ietf-oauth-device-flow-15#section-3.2
-        // The alias is present as Google use a non-standard name for verification_uri.
+        // The aliases are present as Google uses non-standard names for verification_uri and
+        // verification_uri_complete.
         // According to the standard interval is optional, however, all tested implementations provide it.
-        // verification_uri_complete is optional in the standard but not provided in tested implementations.
+        // verification_uri_complete is optional in the standard and not every provider sends it.
         #[derive(Deserialize)]
         struct JsonData {
             device_code: String,
             user_code: String,
             #[serde(alias = "verification_url")]
             verification_uri: String,
+            #[serde(alias = "verification_url_complete", default)]
+            verification_uri_complete: Option<String>,
             expires_in: Option<i64>,
             interval: i64,
         }
@@ -159,8 +282,9 @@ impl DeviceFlow {
         let pi = PollInformation {
             user_code: decoded.user_code,
             verification_url: decoded.verification_uri,
+            verification_url_complete: decoded.verification_uri_complete,
             expires_at: Utc::now() + chrono::Duration::seconds(expires_in),
-            interval: Duration::from_secs(i64::abs(decoded.interval) as u64),
+            interval: Duration::from_secs(decoded.interval.unsigned_abs()),
         };
         Ok((pi, decoded.device_code))
     }
@@ -188,29 +312,77 @@ impl DeviceFlow {
         client: &hyper::Client<C>,
         device_code: &str,
         grant_type: &str,
+        provider: &DeviceFlowProvider,
     ) -> Result<Token, Error>
     where
         C: hyper::client::connect::Connect + 'static,
     {
         // We should be ready for a new request
+        let mut pairs = vec![
+            ("client_id", application_secret.client_id.as_str()),
+            ("code", device_code),
+            ("grant_type", grant_type),
+        ];
+        if provider.send_client_secret {
+            pairs.push(("client_secret", application_secret.client_secret.as_str()));
+        }
         let req = form_urlencoded::Serializer::new(String::new())
-            .extend_pairs(&[
-                ("client_id", application_secret.client_id.as_str()),
-                ("client_secret", application_secret.client_secret.as_str()),
-                ("code", device_code),
-                ("grant_type", grant_type),
-            ])
+            .extend_pairs(&pairs)
             .finish();
 
-        let request = hyper::Request::post(&application_secret.token_uri)
-            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-            .body(hyper::Body::from(req))
-            .unwrap(); // TODO: Error checking
+        let mut builder = hyper::Request::post(&application_secret.token_uri)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+        builder = Self::apply_provider_headers(builder, provider);
+        let request = builder.body(hyper::Body::from(req)).unwrap(); // TODO: Error checking
         let res = client.request(request).await?;
+        // Some providers signal the new polling interval via a `Retry-After` header rather
+        // than (or in addition to) the error body's `interval` field. We only understand the
+        // delay-seconds form (RFC 7231 §7.1.3); a server sending the HTTP-date form is treated
+        // as if it sent nothing, and we fall back to `interval`/the fixed backoff instead.
+        let retry_after = res
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs);
         let body = res.into_body().try_concat().await?;
-        let mut t = serde_json::from_slice::<AuthErrorOr<Token>>(&body)?.into_result()?;
-        t.set_expiry_absolute();
-        Ok(t)
+        match serde_json::from_slice::<AuthErrorOr<Token>>(&body)? {
+            AuthErrorOr::Result(mut t) => {
+                t.set_expiry_absolute();
+                Ok(t)
+            }
+            AuthErrorOr::Err(mut auth_error) => {
+                auth_error.retry_after = retry_after;
+                Err(Error::AuthError(auth_error))
+            }
+        }
+    }
+
+    fn apply_provider_headers(
+        mut builder: hyper::http::request::Builder,
+        provider: &DeviceFlowProvider,
+    ) -> hyper::http::request::Builder {
+        if let Some(user_agent) = &provider.user_agent {
+            builder = builder.header(header::USER_AGENT, user_agent.as_ref());
+        }
+        if provider.accept_json {
+            builder = builder.header(header::ACCEPT, "application/json");
+        }
+        builder
+    }
+
+    /// Picks the interval to poll at next after a `slow_down` response: prefer the `Retry-After`
+    /// header, then the error body's `interval` field, falling back to the current interval
+    /// plus a fixed 5 second backoff when the server gave us nothing to go on.
+    fn next_slow_down_interval(current: Duration, auth_error: &AuthError) -> Duration {
+        auth_error
+            .retry_after
+            .or_else(|| {
+                auth_error
+                    .interval
+                    .map(|secs| Duration::from_secs(secs.unsigned_abs()))
+            })
+            .unwrap_or(current + Duration::from_secs(5))
     }
 }
 
@@ -227,6 +399,10 @@ mod tests {
         impl DeviceFlowDelegate for FD {
             fn present_user_code(&self, pi: &PollInformation) {
                 assert_eq!("https://example.com/verify", pi.verification_url);
+                assert_eq!(
+                    Some("https://example.com/verify?user_code=usercode".to_string()),
+                    pi.verification_url_complete
+                );
             }
         }
 
@@ -252,6 +428,7 @@ mod tests {
             device_code_url: device_code_url.into(),
             flow_delegate: Box::new(FD),
             grant_type: GOOGLE_GRANT_TYPE.into(),
+            provider: DeviceFlowProvider::default(),
         };
 
         // Successful path
@@ -260,6 +437,7 @@ mod tests {
                 "device_code": "devicecode",
                 "user_code": "usercode",
                 "verification_url": "https://example.com/verify",
+                "verification_url_complete": "https://example.com/verify?user_code=usercode",
                 "expires_in": 1234567,
                 "interval": 1
             });
@@ -357,9 +535,278 @@ mod tests {
             let res = flow
                 .token(&client, &["https://www.googleapis.com/scope/1"])
                 .await;
-            assert!(res.is_err());
-            assert!(format!("{}", res.unwrap_err()).contains("access_denied"));
+            assert!(matches!(
+                res,
+                Err(Error::DeviceFlow(DeviceFlowError::AccessDenied))
+            ));
             _m.assert();
         }
     }
+
+    #[test]
+    fn test_with_provider_switches_device_code_url_and_grant_type() {
+        let app_secret: ApplicationSecret = crate::parse_json!({
+            "client_id": "github-client-id",
+            "project_id": "yup-test-243420",
+            "auth_uri": "https://github.com/login/oauth/authorize",
+            "token_uri": "https://github.com/login/oauth/access_token",
+            "auth_provider_x509_cert_url": "https://www.googleapis.com/oauth2/v1/certs",
+            "client_secret": "unused-for-github",
+            "redirect_uris": ["urn:ietf:wg:oauth:2.0:oob","http://localhost"],
+        });
+
+        let flow = DeviceFlow::new(app_secret).with_provider(DeviceFlowProvider::GITHUB);
+
+        assert_eq!(GITHUB_DEVICE_CODE_URL, flow.device_code_url);
+        assert_eq!(GITHUB_GRANT_TYPE, flow.grant_type);
+        assert!(!flow.provider.send_client_secret);
+        assert!(flow.provider.accept_json);
+    }
+
+    #[tokio::test]
+    async fn test_device_end2end_github_provider() {
+        let server_url = mockito::server_url();
+        let app_secret: ApplicationSecret = crate::parse_json!({
+            "client_id": "github-client-id",
+            "project_id": "yup-test-243420",
+            "auth_uri": "https://github.com/login/oauth/authorize",
+            "token_uri": format!("{}/login/oauth/access_token", server_url),
+            "auth_provider_x509_cert_url": "https://www.googleapis.com/oauth2/v1/certs",
+            "client_secret": "unused-for-github",
+            "redirect_uris": ["urn:ietf:wg:oauth:2.0:oob","http://localhost"],
+        });
+
+        let https = HttpsConnector::new();
+        let client = hyper::Client::builder()
+            .keep_alive(false)
+            .build::<_, hyper::Body>(https);
+
+        // Exercises the actual public integration point: `DeviceFlow::new(...)` plus
+        // `with_provider(...)` with only `device_code_url` overridden to point at the mock
+        // server instead of the real github.com endpoint.
+        let provider = DeviceFlowProvider {
+            device_code_url: format!("{}/login/device/code", server_url).into(),
+            ..DeviceFlowProvider::GITHUB
+        };
+        let flow = DeviceFlow::new(app_secret).with_provider(provider);
+
+        let code_response = serde_json::json!({
+            "device_code": "devicecode",
+            "user_code": "usercode",
+            "verification_uri": "https://github.com/login/device",
+            "expires_in": 1234567,
+            "interval": 1
+        });
+        let _m = mockito::mock("POST", "/login/device/code")
+            .match_header("user-agent", "yup-oauth2")
+            .match_header("accept", "application/json")
+            .match_body(mockito::Matcher::Regex(
+                ".*client_id=github-client-id.*".to_string(),
+            ))
+            .with_status(200)
+            .with_body(code_response.to_string())
+            .create();
+        let token_response = serde_json::json!({
+            "access_token": "accesstoken",
+            "refresh_token": "refreshtoken",
+            "token_type": "Bearer",
+            "expires_in": 1234567
+        });
+        // Anchored so the body must be *exactly* these three fields: any `client_secret`
+        // GitHub doesn't expect (it's a public client) would fail this match.
+        let _m = mockito::mock("POST", "/login/oauth/access_token")
+            .match_header("user-agent", "yup-oauth2")
+            .match_header("accept", "application/json")
+            .match_body(mockito::Matcher::Regex(format!(
+                "^client_id=github-client-id&code=devicecode&grant_type={}$",
+                GITHUB_GRANT_TYPE.replace(':', "%3A")
+            )))
+            .with_status(200)
+            .with_body(token_response.to_string())
+            .create();
+
+        let token = flow.token(&client, &["repo"]).await.expect("token failed");
+        assert_eq!("accesstoken", token.access_token);
+        _m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_device_token_expires_without_polling_forever() {
+        struct FD;
+        impl DeviceFlowDelegate for FD {
+            fn present_user_code(&self, _pi: &PollInformation) {}
+        }
+
+        let app_secret: ApplicationSecret = crate::parse_json!({
+            "client_id": "902216714886-k2v9uei3p1dk6h686jbsn9mo96tnbvto.apps.googleusercontent.com",
+            "project_id": "yup-test-243420",
+            "auth_uri": "https://accounts.google.com/o/oauth2/auth",
+            "token_uri": "https://unused.example.com/token",
+            "auth_provider_x509_cert_url": "https://www.googleapis.com/oauth2/v1/certs",
+            "client_secret": "iuMPN6Ne1PD7cos29Tk9rlqH",
+            "redirect_uris": ["urn:ietf:wg:oauth:2.0:oob","http://localhost"],
+        });
+
+        let https = HttpsConnector::new();
+        let client = hyper::Client::builder()
+            .keep_alive(false)
+            .build::<_, hyper::Body>(https);
+
+        let flow = DeviceFlow {
+            app_secret: app_secret.clone(),
+            device_code_url: "https://unused.example.com/code".into(),
+            flow_delegate: Box::new(FD),
+            grant_type: GOOGLE_GRANT_TYPE.into(),
+            provider: DeviceFlowProvider::default(),
+        };
+
+        // Already past the deadline, so we must bail before ever polling the token endpoint.
+        let pollinf = PollInformation {
+            user_code: "usercode".to_string(),
+            verification_url: "https://example.com/verify".to_string(),
+            verification_url_complete: None,
+            expires_at: Utc::now() - chrono::Duration::seconds(1),
+            interval: Duration::from_secs(60),
+        };
+
+        let res = flow
+            .wait_for_device_token(
+                &client,
+                &app_secret,
+                &pollinf,
+                "devicecode",
+                GOOGLE_GRANT_TYPE,
+                &DeviceFlowProvider::default(),
+            )
+            .await;
+        assert!(matches!(res, Err(Error::DeviceFlowExpired)));
+    }
+
+    #[tokio::test]
+    async fn test_request_code_parses_standard_verification_uri_complete_field() {
+        let server_url = mockito::server_url();
+        let app_secret: ApplicationSecret = crate::parse_json!({
+            "client_id": "902216714886-k2v9uei3p1dk6h686jbsn9mo96tnbvto.apps.googleusercontent.com",
+            "project_id": "yup-test-243420",
+            "auth_uri": "https://accounts.google.com/o/oauth2/auth",
+            "token_uri": format!("{}/token", server_url),
+            "auth_provider_x509_cert_url": "https://www.googleapis.com/oauth2/v1/certs",
+            "client_secret": "iuMPN6Ne1PD7cos29Tk9rlqH",
+            "redirect_uris": ["urn:ietf:wg:oauth:2.0:oob","http://localhost"],
+        });
+        let device_code_url = format!("{}/code", server_url);
+
+        let https = HttpsConnector::new();
+        let client = hyper::Client::builder()
+            .keep_alive(false)
+            .build::<_, hyper::Body>(https);
+
+        let code_response = serde_json::json!({
+            "device_code": "devicecode",
+            "user_code": "usercode",
+            "verification_url": "https://example.com/verify",
+            "verification_uri_complete": "https://example.com/verify?user_code=usercode",
+            "expires_in": 1234567,
+            "interval": 1
+        });
+        let _m = mockito::mock("POST", "/code")
+            .with_status(200)
+            .with_body(code_response.to_string())
+            .create();
+
+        let (pi, _device_code) = DeviceFlow::request_code(
+            &app_secret,
+            &client,
+            &device_code_url,
+            &DeviceFlowProvider::default(),
+            &["https://www.googleapis.com/scope/1"],
+        )
+        .await
+        .expect("request_code failed");
+
+        assert_eq!(
+            Some("https://example.com/verify?user_code=usercode".to_string()),
+            pi.verification_url_complete
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_code_verification_uri_complete_absent_is_none() {
+        let server_url = mockito::server_url();
+        let app_secret: ApplicationSecret = crate::parse_json!({
+            "client_id": "902216714886-k2v9uei3p1dk6h686jbsn9mo96tnbvto.apps.googleusercontent.com",
+            "project_id": "yup-test-243420",
+            "auth_uri": "https://accounts.google.com/o/oauth2/auth",
+            "token_uri": format!("{}/token", server_url),
+            "auth_provider_x509_cert_url": "https://www.googleapis.com/oauth2/v1/certs",
+            "client_secret": "iuMPN6Ne1PD7cos29Tk9rlqH",
+            "redirect_uris": ["urn:ietf:wg:oauth:2.0:oob","http://localhost"],
+        });
+        let device_code_url = format!("{}/code", server_url);
+
+        let https = HttpsConnector::new();
+        let client = hyper::Client::builder()
+            .keep_alive(false)
+            .build::<_, hyper::Body>(https);
+
+        let code_response = serde_json::json!({
+            "device_code": "devicecode",
+            "user_code": "usercode",
+            "verification_url": "https://example.com/verify",
+            "expires_in": 1234567,
+            "interval": 1
+        });
+        let _m = mockito::mock("POST", "/code")
+            .with_status(200)
+            .with_body(code_response.to_string())
+            .create();
+
+        let (pi, _device_code) = DeviceFlow::request_code(
+            &app_secret,
+            &client,
+            &device_code_url,
+            &DeviceFlowProvider::default(),
+            &["https://www.googleapis.com/scope/1"],
+        )
+        .await
+        .expect("request_code failed");
+
+        assert_eq!(None, pi.verification_url_complete);
+    }
+
+    fn slow_down_error(interval: Option<i64>, retry_after: Option<Duration>) -> AuthError {
+        AuthError {
+            error: "slow_down".to_string(),
+            error_description: None,
+            interval,
+            retry_after,
+        }
+    }
+
+    #[test]
+    fn test_next_slow_down_interval_prefers_retry_after_header() {
+        let auth_error = slow_down_error(Some(10), Some(Duration::from_secs(30)));
+        assert_eq!(
+            Duration::from_secs(30),
+            DeviceFlow::next_slow_down_interval(Duration::from_secs(1), &auth_error)
+        );
+    }
+
+    #[test]
+    fn test_next_slow_down_interval_falls_back_to_body_interval() {
+        let auth_error = slow_down_error(Some(10), None);
+        assert_eq!(
+            Duration::from_secs(10),
+            DeviceFlow::next_slow_down_interval(Duration::from_secs(1), &auth_error)
+        );
+    }
+
+    #[test]
+    fn test_next_slow_down_interval_falls_back_to_fixed_backoff() {
+        let auth_error = slow_down_error(None, None);
+        assert_eq!(
+            Duration::from_secs(6),
+            DeviceFlow::next_slow_down_interval(Duration::from_secs(1), &auth_error)
+        );
+    }
 }
@@ -0,0 +1,112 @@
+//! Error types returned by this crate.
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::device::DeviceFlowError;
+
+/// An error as returned by an OAuth2 token or device-code endpoint, as described in
+/// [RFC 6749 §5.2](https://tools.ietf.org/html/rfc6749#section-5.2).
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthError {
+    pub error: String,
+    pub error_description: Option<String>,
+    /// A server-suggested new polling interval, in seconds, present on some `slow_down`
+    /// responses. Not part of RFC 6749, but returned by several device-flow implementations.
+    #[serde(default)]
+    pub interval: Option<i64>,
+    /// The `Retry-After` response header, if the server sent one. Not deserialized from the
+    /// response body; set by the caller after inspecting the response headers.
+    #[serde(skip)]
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        if let Some(desc) = &self.error_description {
+            write!(f, ": {}", desc)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for AuthError {}
+
+/// Helper for deserializing a response that is either the expected payload `T` or an
+/// [`AuthError`], without knowing ahead of time which one the server sent.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum AuthErrorOr<T> {
+    Err(AuthError),
+    Result(T),
+}
+
+impl<T> AuthErrorOr<T> {
+    pub(crate) fn into_result(self) -> Result<T, Error> {
+        match self {
+            AuthErrorOr::Err(e) => Err(Error::AuthError(e)),
+            AuthErrorOr::Result(t) => Ok(t),
+        }
+    }
+}
+
+/// Encapsulates all possible results of the `token(...)` operation.
+#[derive(Debug)]
+pub enum Error {
+    /// Indicates connection failure
+    HttpError(hyper::Error),
+    /// A malformed server response.
+    JSONError(serde_json::Error),
+    /// The server responded with an OAuth2 error.
+    AuthError(AuthError),
+    /// The device code obtained via `DeviceFlow::request_code` expired before the user
+    /// completed authorization, as determined locally by comparing against
+    /// `PollInformation::expires_at`. The server-reported equivalent, returned once it also
+    /// considers the code expired, is `Error::DeviceFlow(DeviceFlowError::ExpiredToken)`.
+    DeviceFlowExpired,
+    /// The device flow's token endpoint returned a terminal, RFC 8628 §3.5 error (e.g. the
+    /// user denied access, or the server considers the device code expired via
+    /// `DeviceFlowError::ExpiredToken`, the server-reported counterpart to the locally-detected
+    /// `Error::DeviceFlowExpired`).
+    DeviceFlow(DeviceFlowError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::HttpError(err) => err.fmt(f),
+            Error::JSONError(err) => err.fmt(f),
+            Error::AuthError(err) => err.fmt(f),
+            Error::DeviceFlowExpired => write!(f, "the device code expired before authorization"),
+            Error::DeviceFlow(err) => err.fmt(f),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::HttpError(err) => Some(err),
+            Error::JSONError(err) => Some(err),
+            Error::AuthError(err) => Some(err),
+            Error::DeviceFlowExpired => None,
+            Error::DeviceFlow(_) => None,
+        }
+    }
+}
+
+impl From<hyper::Error> for Error {
+    fn from(err: hyper::Error) -> Self {
+        Error::HttpError(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::JSONError(err)
+    }
+}